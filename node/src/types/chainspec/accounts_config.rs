@@ -1,18 +1,26 @@
-use std::path::Path;
+use std::{collections::HashSet, convert::TryFrom, path::Path};
 
 use datasize::DataSize;
 use num::Zero;
+#[cfg(any(feature = "testing", test))]
+use proptest::{collection::vec, prelude::*, sample::select};
+// `rand::prelude::*` (below) already re-exports `Rng`/`SeedableRng` in test builds, so the
+// explicit import is only needed outside of `#[cfg(test)]`.
+#[cfg(not(test))]
+use rand::{Rng, SeedableRng};
 #[cfg(test)]
 use rand::{distributions::Standard, prelude::*};
+use rand_chacha::ChaCha20Rng;
 use serde::{Deserialize, Serialize};
 
 use casper_execution_engine::{core::engine_state::GenesisAccount, shared::motes::Motes};
+use casper_hashing::Digest;
 use casper_types::{
     bytesrepr::{self, FromBytes, ToBytes},
-    PublicKey,
+    PublicKey, SecretKey,
 };
-#[cfg(test)]
-use casper_types::{SecretKey, U512};
+#[cfg(any(feature = "testing", test))]
+use casper_types::U512;
 
 #[cfg(test)]
 use crate::testing::TestRng;
@@ -85,9 +93,45 @@ impl Distribution<AccountConfig> for Standard {
     }
 }
 
+#[cfg(any(feature = "testing", test))]
+impl Arbitrary for AccountConfig {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        (any::<[u8; 32]>(), any::<u64>(), any::<u64>())
+            .prop_map(|(seed, balance, bonded_amount)| {
+                AccountConfig::new(
+                    PublicKey::from(&SecretKey::ed25519(seed)),
+                    Motes::new(U512::from(balance)),
+                    Motes::new(U512::from(bonded_amount)),
+                )
+            })
+            .boxed()
+    }
+}
+
+impl AccountConfig {
+    /// The current binary format version for `AccountConfig`.
+    const CURRENT_VERSION: u16 = 1;
+
+    fn from_bytes_v1(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (public_key, remainder) = FromBytes::from_bytes(bytes)?;
+        let (balance, remainder) = FromBytes::from_bytes(remainder)?;
+        let (bonded_amount, remainder) = FromBytes::from_bytes(remainder)?;
+        let account_config = AccountConfig {
+            public_key,
+            balance,
+            bonded_amount,
+        };
+        Ok((account_config, remainder))
+    }
+}
+
 impl ToBytes for AccountConfig {
     fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
         let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(Self::CURRENT_VERSION.to_bytes()?);
         buffer.extend(self.public_key.to_bytes()?);
         buffer.extend(self.balance.to_bytes()?);
         buffer.extend(self.bonded_amount.to_bytes()?);
@@ -95,7 +139,8 @@ impl ToBytes for AccountConfig {
     }
 
     fn serialized_length(&self) -> usize {
-        self.public_key.serialized_length()
+        Self::CURRENT_VERSION.serialized_length()
+            + self.public_key.serialized_length()
             + self.balance.serialized_length()
             + self.bonded_amount.serialized_length()
     }
@@ -103,15 +148,14 @@ impl ToBytes for AccountConfig {
 
 impl FromBytes for AccountConfig {
     fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
-        let (public_key, remainder) = FromBytes::from_bytes(bytes)?;
-        let (balance, remainder) = FromBytes::from_bytes(remainder)?;
-        let (bonded_amount, remainder) = FromBytes::from_bytes(remainder)?;
-        let account_config = AccountConfig {
-            public_key,
-            balance,
-            bonded_amount,
-        };
-        Ok((account_config, remainder))
+        let (version, remainder) = u16::from_bytes(bytes)?;
+        match version {
+            Self::CURRENT_VERSION => Self::from_bytes_v1(remainder),
+            // `bytesrepr::Error` has no payload to carry the offending version number, so an
+            // unrecognised version tag is reported via the same `Formatting` variant used for
+            // other malformed encodings.
+            _ => Err(bytesrepr::Error::Formatting),
+        }
     }
 }
 
@@ -193,9 +237,55 @@ impl Distribution<DelegatorConfig> for Standard {
     }
 }
 
+#[cfg(any(feature = "testing", test))]
+impl Arbitrary for DelegatorConfig {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        (
+            any::<[u8; 32]>(),
+            any::<[u8; 32]>(),
+            any::<u64>(),
+            any::<u64>(),
+        )
+            .prop_map(
+                |(validator_seed, delegator_seed, balance, delegated_amount)| {
+                    DelegatorConfig::new(
+                        PublicKey::from(&SecretKey::ed25519(validator_seed)),
+                        PublicKey::from(&SecretKey::ed25519(delegator_seed)),
+                        Motes::new(U512::from(balance)),
+                        Motes::new(U512::from(delegated_amount)),
+                    )
+                },
+            )
+            .boxed()
+    }
+}
+
+impl DelegatorConfig {
+    /// The current binary format version for `DelegatorConfig`.
+    const CURRENT_VERSION: u16 = 1;
+
+    fn from_bytes_v1(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (validator_public_key, remainder) = FromBytes::from_bytes(bytes)?;
+        let (delegator_public_key, remainder) = FromBytes::from_bytes(remainder)?;
+        let (balance, remainder) = FromBytes::from_bytes(remainder)?;
+        let (delegated_amount, remainder) = FromBytes::from_bytes(remainder)?;
+        let delegator_config = DelegatorConfig {
+            validator_public_key,
+            delegator_public_key,
+            balance,
+            delegated_amount,
+        };
+        Ok((delegator_config, remainder))
+    }
+}
+
 impl ToBytes for DelegatorConfig {
     fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
         let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(Self::CURRENT_VERSION.to_bytes()?);
         buffer.extend(self.validator_public_key.to_bytes()?);
         buffer.extend(self.delegator_public_key.to_bytes()?);
         buffer.extend(self.balance.to_bytes()?);
@@ -204,7 +294,8 @@ impl ToBytes for DelegatorConfig {
     }
 
     fn serialized_length(&self) -> usize {
-        self.validator_public_key.serialized_length()
+        Self::CURRENT_VERSION.serialized_length()
+            + self.validator_public_key.serialized_length()
             + self.delegator_public_key.serialized_length()
             + self.balance.serialized_length()
             + self.delegated_amount.serialized_length()
@@ -213,20 +304,35 @@ impl ToBytes for DelegatorConfig {
 
 impl FromBytes for DelegatorConfig {
     fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
-        let (validator_public_key, remainder) = FromBytes::from_bytes(bytes)?;
-        let (delegator_public_key, remainder) = FromBytes::from_bytes(remainder)?;
-        let (balance, remainder) = FromBytes::from_bytes(remainder)?;
-        let (delegated_amount, remainder) = FromBytes::from_bytes(remainder)?;
-        let delegator_config = DelegatorConfig {
-            validator_public_key,
-            delegator_public_key,
-            balance,
-            delegated_amount,
-        };
-        Ok((delegator_config, remainder))
+        let (version, remainder) = u16::from_bytes(bytes)?;
+        match version {
+            Self::CURRENT_VERSION => Self::from_bytes_v1(remainder),
+            // `bytesrepr::Error` has no payload to carry the offending version number, so an
+            // unrecognised version tag is reported via the same `Formatting` variant used for
+            // other malformed encodings.
+            _ => Err(bytesrepr::Error::Formatting),
+        }
     }
 }
 
+/// Parameters controlling [`AccountsConfig::generate`] and
+/// [`AccountsConfig::generate_from_passphrase`].
+#[derive(Debug, Clone, Copy)]
+pub struct GenesisAccountsGeneratorConfig {
+    /// Number of validator accounts to generate.
+    pub validator_count: usize,
+    /// Balance assigned to every generated validator.
+    pub validator_balance: Motes,
+    /// Bonded amount assigned to every generated validator.
+    pub validator_bonded_amount: Motes,
+    /// Number of delegator accounts to generate.
+    pub delegator_count: usize,
+    /// Balance assigned to every generated delegator.
+    pub delegator_balance: Motes,
+    /// Amount each generated delegator delegates to its validator.
+    pub delegator_delegated_amount: Motes,
+}
+
 #[derive(PartialEq, Eq, Serialize, Deserialize, DataSize, Debug, Clone)]
 pub struct AccountsConfig {
     accounts: Vec<AccountConfig>,
@@ -242,6 +348,77 @@ impl AccountsConfig {
         }
     }
 
+    /// Deterministically generates a fully populated `AccountsConfig` with freshly derived
+    /// keypairs, rather than requiring operators to hand-edit `accounts.toml`. Delegators are
+    /// wired to validators round-robin.
+    ///
+    /// The same `rng` seed always yields the same public keys, so a genesis set generated this
+    /// way is reproducible across machines. Returns the generated config along with the secret
+    /// keys so operators can distribute them.
+    ///
+    /// Returns [`ChainspecAccountsLoadError::InvalidGeneratorConfig`] if `config` requests
+    /// delegators but no validators for them to delegate to.
+    pub fn generate<R: Rng + ?Sized>(
+        rng: &mut R,
+        config: GenesisAccountsGeneratorConfig,
+    ) -> Result<(Self, Vec<SecretKey>), ChainspecAccountsLoadError> {
+        if config.validator_count == 0 && config.delegator_count > 0 {
+            return Err(ChainspecAccountsLoadError::InvalidGeneratorConfig {
+                delegator_count: config.delegator_count,
+            });
+        }
+
+        let mut secret_keys = Vec::with_capacity(config.validator_count + config.delegator_count);
+
+        let mut accounts = Vec::with_capacity(config.validator_count);
+        let mut validator_public_keys = Vec::with_capacity(config.validator_count);
+        for _ in 0..config.validator_count {
+            let secret_key = SecretKey::ed25519(rng.gen());
+            let public_key = PublicKey::from(&secret_key);
+            validator_public_keys.push(public_key);
+            accounts.push(AccountConfig::new(
+                public_key,
+                config.validator_balance,
+                config.validator_bonded_amount,
+            ));
+            secret_keys.push(secret_key);
+        }
+
+        let mut delegators = Vec::with_capacity(config.delegator_count);
+        for index in 0..config.delegator_count {
+            let secret_key = SecretKey::ed25519(rng.gen());
+            let delegator_public_key = PublicKey::from(&secret_key);
+            let validator_public_key = validator_public_keys[index % config.validator_count];
+            delegators.push(DelegatorConfig::new(
+                validator_public_key,
+                delegator_public_key,
+                config.delegator_balance,
+                config.delegator_delegated_amount,
+            ));
+            secret_keys.push(secret_key);
+        }
+
+        Ok((AccountsConfig::new(accounts, delegators), secret_keys))
+    }
+
+    /// Derives a 32-byte seed from a human-readable passphrase (in the style of a brain wallet)
+    /// and uses it to seed a [`ChaCha20Rng`] fed to [`AccountsConfig::generate`], so a genesis
+    /// set can be regenerated byte-for-byte on any machine from the same short passphrase.
+    ///
+    /// `ChaCha20Rng` is used rather than `rand::rngs::StdRng` because `StdRng` is explicitly
+    /// documented as *not* value-stable: its underlying algorithm may change between `rand`
+    /// releases, which would silently change the derived keys for the same passphrase after a
+    /// dependency bump. `ChaCha20Rng`'s output is guaranteed stable for a given major version of
+    /// `rand_chacha`, which is what makes the byte-for-byte reproducibility guarantee above hold.
+    pub fn generate_from_passphrase(
+        passphrase: &str,
+        config: GenesisAccountsGeneratorConfig,
+    ) -> Result<(Self, Vec<SecretKey>), ChainspecAccountsLoadError> {
+        let seed = Digest::hash(passphrase.as_bytes()).value();
+        let mut rng = ChaCha20Rng::from_seed(seed);
+        Self::generate(&mut rng, config)
+    }
+
     pub fn accounts(&self) -> &[AccountConfig] {
         &self.accounts
     }
@@ -273,25 +450,80 @@ impl AccountsConfig {
     }
 }
 
+#[cfg(any(feature = "testing", test))]
+impl Arbitrary for AccountsConfig {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        vec(any::<AccountConfig>(), 1..10)
+            .prop_flat_map(|accounts| {
+                let validator_public_keys: Vec<PublicKey> =
+                    accounts.iter().map(AccountConfig::public_key).collect();
+                let delegators = vec(
+                    (
+                        select(validator_public_keys),
+                        any::<[u8; 32]>(),
+                        any::<u64>(),
+                        any::<u64>(),
+                    )
+                        .prop_map(
+                            |(validator_public_key, delegator_seed, balance, delegated_amount)| {
+                                DelegatorConfig::new(
+                                    validator_public_key,
+                                    PublicKey::from(&SecretKey::ed25519(delegator_seed)),
+                                    Motes::new(U512::from(balance)),
+                                    Motes::new(U512::from(delegated_amount)),
+                                )
+                            },
+                        ),
+                    0..5,
+                );
+                (Just(accounts), delegators)
+            })
+            .prop_map(|(accounts, delegators)| AccountsConfig::new(accounts, delegators))
+            .boxed()
+    }
+}
+
+impl AccountsConfig {
+    /// The current binary format version for `AccountsConfig`.
+    const CURRENT_VERSION: u16 = 1;
+
+    fn from_bytes_v1(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (accounts, remainder) = FromBytes::from_bytes(bytes)?;
+        let (delegators, remainder) = FromBytes::from_bytes(remainder)?;
+        let accounts_config = AccountsConfig::new(accounts, delegators);
+        Ok((accounts_config, remainder))
+    }
+}
+
 impl ToBytes for AccountsConfig {
     fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
         let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(Self::CURRENT_VERSION.to_bytes()?);
         buffer.extend(self.accounts.to_bytes()?);
         buffer.extend(self.delegators.to_bytes()?);
         Ok(buffer)
     }
 
     fn serialized_length(&self) -> usize {
-        self.accounts.serialized_length() + self.delegators.serialized_length()
+        Self::CURRENT_VERSION.serialized_length()
+            + self.accounts.serialized_length()
+            + self.delegators.serialized_length()
     }
 }
 
 impl FromBytes for AccountsConfig {
     fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
-        let (accounts, remainder) = FromBytes::from_bytes(bytes)?;
-        let (delegators, remainder) = FromBytes::from_bytes(remainder)?;
-        let accounts_config = AccountsConfig::new(accounts, delegators);
-        Ok((accounts_config, remainder))
+        let (version, remainder) = u16::from_bytes(bytes)?;
+        match version {
+            Self::CURRENT_VERSION => Self::from_bytes_v1(remainder),
+            // `bytesrepr::Error` has no payload to carry the offending version number, so an
+            // unrecognised version tag is reported via the same `Formatting` variant used for
+            // other malformed encodings.
+            _ => Err(bytesrepr::Error::Formatting),
+        }
     }
 }
 
@@ -309,9 +541,20 @@ impl Loadable for AccountsConfig {
     }
 }
 
-impl From<AccountsConfig> for Vec<GenesisAccount> {
-    fn from(accounts_config: AccountsConfig) -> Self {
-        let mut genesis_accounts = Vec::with_capacity(accounts_config.accounts.len());
+impl TryFrom<AccountsConfig> for Vec<GenesisAccount> {
+    type Error = ChainspecAccountsLoadError;
+
+    fn try_from(accounts_config: AccountsConfig) -> Result<Self, Self::Error> {
+        let validator_public_keys: HashSet<PublicKey> = accounts_config
+            .accounts
+            .iter()
+            .map(AccountConfig::public_key)
+            .collect();
+
+        let mut genesis_accounts = Vec::with_capacity(
+            accounts_config.accounts.len() + accounts_config.delegators.len(),
+        );
+
         for account in accounts_config.accounts {
             let genesis_account = GenesisAccount::new(
                 account.public_key,
@@ -321,7 +564,23 @@ impl From<AccountsConfig> for Vec<GenesisAccount> {
             );
             genesis_accounts.push(genesis_account);
         }
-        genesis_accounts
+
+        for delegator in accounts_config.delegators {
+            if !validator_public_keys.contains(&delegator.validator_public_key) {
+                return Err(ChainspecAccountsLoadError::UnknownValidatorPublicKey {
+                    validator_public_key: delegator.validator_public_key,
+                });
+            }
+            let genesis_delegator = GenesisAccount::delegator(
+                delegator.validator_public_key,
+                delegator.delegator_public_key,
+                delegator.balance,
+                delegator.delegated_amount,
+            );
+            genesis_accounts.push(genesis_delegator);
+        }
+
+        Ok(genesis_accounts)
     }
 }
 
@@ -335,4 +594,98 @@ mod tests {
         let accounts_config = AccountsConfig::random(&mut rng);
         bytesrepr::test_serialization_roundtrip(&accounts_config);
     }
+
+    #[test]
+    fn should_reject_unknown_version() {
+        let mut rng = TestRng::new();
+        let accounts_config = AccountsConfig::random(&mut rng);
+        let mut bytes = accounts_config.to_bytes().unwrap();
+        // Corrupt the leading format-version tag so it no longer matches a known decoder.
+        bytes[0] = u8::MAX;
+        bytes[1] = u8::MAX;
+        assert!(AccountsConfig::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn try_from_should_include_delegators_in_genesis_accounts() {
+        let mut rng = TestRng::new();
+        let accounts_config = AccountsConfig::random(&mut rng);
+        let delegator_count = accounts_config.delegators().len();
+        assert!(
+            delegator_count > 0,
+            "fixture should include at least one delegator"
+        );
+
+        let genesis_accounts = Vec::<GenesisAccount>::try_from(accounts_config).unwrap();
+
+        let carried_delegator_count = genesis_accounts
+            .iter()
+            .filter(|genesis_account| matches!(genesis_account, GenesisAccount::Delegator { .. }))
+            .count();
+        assert_eq!(carried_delegator_count, delegator_count);
+    }
+
+    #[test]
+    fn try_from_should_reject_delegator_with_unknown_validator() {
+        let mut rng = TestRng::new();
+        let accounts_config = AccountsConfig::random(&mut rng);
+        let unknown_validator_public_key = PublicKey::from(&SecretKey::ed25519(rng.gen()));
+        let mut delegator = accounts_config.delegators()[0];
+        delegator.validator_public_key = unknown_validator_public_key;
+        let accounts_config =
+            AccountsConfig::new(accounts_config.accounts().to_vec(), vec![delegator]);
+
+        let result = Vec::<GenesisAccount>::try_from(accounts_config);
+
+        assert!(matches!(
+            result,
+            Err(ChainspecAccountsLoadError::UnknownValidatorPublicKey { validator_public_key })
+                if validator_public_key == unknown_validator_public_key
+        ));
+    }
+
+    #[test]
+    fn generate_should_be_deterministic_from_passphrase() {
+        let config = GenesisAccountsGeneratorConfig {
+            validator_count: 3,
+            validator_balance: Motes::new(U512::from(1_000_000_000u64)),
+            validator_bonded_amount: Motes::new(U512::from(1_000_000u64)),
+            delegator_count: 5,
+            delegator_balance: Motes::new(U512::from(500_000u64)),
+            delegator_delegated_amount: Motes::new(U512::from(10_000u64)),
+        };
+
+        let (first_accounts_config, first_secret_keys) =
+            AccountsConfig::generate_from_passphrase("correct horse battery staple", config)
+                .unwrap();
+        let (second_accounts_config, second_secret_keys) =
+            AccountsConfig::generate_from_passphrase("correct horse battery staple", config)
+                .unwrap();
+
+        assert_eq!(first_accounts_config, second_accounts_config);
+
+        let first_public_keys: Vec<PublicKey> =
+            first_secret_keys.iter().map(PublicKey::from).collect();
+        let second_public_keys: Vec<PublicKey> =
+            second_secret_keys.iter().map(PublicKey::from).collect();
+        assert_eq!(first_public_keys, second_public_keys);
+    }
+
+    #[test]
+    fn generate_should_reject_delegators_without_validators() {
+        let mut rng = TestRng::new();
+        let config = GenesisAccountsGeneratorConfig {
+            validator_count: 0,
+            validator_balance: Motes::new(U512::from(0u64)),
+            validator_bonded_amount: Motes::new(U512::from(0u64)),
+            delegator_count: 1,
+            delegator_balance: Motes::new(U512::from(0u64)),
+            delegator_delegated_amount: Motes::new(U512::from(0u64)),
+        };
+
+        assert!(matches!(
+            AccountsConfig::generate(&mut rng, config),
+            Err(ChainspecAccountsLoadError::InvalidGeneratorConfig { delegator_count: 1 })
+        ));
+    }
 }
@@ -0,0 +1,36 @@
+use thiserror::Error;
+
+use casper_types::PublicKey;
+
+use crate::utils::ReadFileError;
+
+/// Error returned while loading or validating the genesis `accounts.toml` chainspec fragment.
+#[derive(Debug, Error)]
+pub enum ChainspecAccountsLoadError {
+    /// Failed to read the `accounts.toml` file.
+    #[error(transparent)]
+    ReadFileError(#[from] ReadFileError),
+
+    /// Failed to parse the `accounts.toml` file as TOML.
+    #[error(transparent)]
+    ParseError(#[from] toml::de::Error),
+
+    /// A delegator refers to a validator public key that isn't present among the configured
+    /// accounts.
+    #[error("delegator refers to unknown validator public key: {validator_public_key}")]
+    UnknownValidatorPublicKey {
+        /// The unrecognised validator public key referenced by a delegator.
+        validator_public_key: PublicKey,
+    },
+
+    /// A [`crate::types::chainspec::accounts_config::GenesisAccountsGeneratorConfig`] requested
+    /// delegators without any validators for them to delegate to.
+    #[error(
+        "cannot generate {delegator_count} delegator(s) without at least one validator to \
+         delegate to"
+    )]
+    InvalidGeneratorConfig {
+        /// The number of delegators that were requested.
+        delegator_count: usize,
+    },
+}